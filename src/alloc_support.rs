@@ -0,0 +1,193 @@
+//! Global allocator backing `OrderBook`'s growable containers when the
+//! `alloc` feature is enabled: a fixed arena, bump-allocated from the front,
+//! with a bucketed free list (indexed by size class) for reuse. A freed span
+//! sitting at the bump frontier is coalesced back into the frontier instead
+//! of free-listed; anything else goes into its size-class bucket, so same-size
+//! reuse is O(1) and odd sizes fall back to a walk of that bucket's list.
+#![cfg(feature = "alloc")]
+
+extern crate alloc;
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::mem::{align_of, size_of};
+use core::ptr;
+
+/// Arena size in bytes. Tune to the deployment's memory budget.
+const ARENA_SIZE: usize = 64 * 1024;
+
+/// Free-list buckets, indexed by `bucket_for(size)`; bucket `i` holds spans
+/// sized in `[2^(i-1), 2^i)` bytes, and the last bucket also holds every
+/// larger span.
+const NUM_BUCKETS: usize = 16;
+
+/// Every served block is at least this big/aligned, since a freed block that
+/// isn't reclaimed at the bump frontier gets a `FreeSpan` written into it.
+const MIN_BLOCK_SIZE: usize = size_of::<FreeSpan>();
+const MIN_BLOCK_ALIGN: usize = align_of::<FreeSpan>();
+
+fn bucket_for(size: usize) -> usize {
+    let bits = usize::BITS - size.max(1).leading_zeros();
+    (bits as usize).min(NUM_BUCKETS - 1)
+}
+
+struct FreeSpan {
+    size: usize,
+    next: *mut FreeSpan,
+}
+
+/// Bump-plus-fallback global allocator. `#[global_allocator]`-safe: all
+/// interior mutability is behind `UnsafeCell` and access is unsynchronized,
+/// which is sound here because PolkaVM contract execution is single-threaded.
+pub struct BumpFallbackAllocator {
+    arena: UnsafeCell<[u8; ARENA_SIZE]>,
+    bump: UnsafeCell<usize>,
+    buckets: UnsafeCell<[*mut FreeSpan; NUM_BUCKETS]>,
+}
+
+unsafe impl Sync for BumpFallbackAllocator {}
+
+impl BumpFallbackAllocator {
+    pub const fn new() -> Self {
+        BumpFallbackAllocator {
+            arena: UnsafeCell::new([0; ARENA_SIZE]),
+            bump: UnsafeCell::new(0),
+            buckets: UnsafeCell::new([ptr::null_mut(); NUM_BUCKETS]),
+        }
+    }
+
+    /// Conservative lower bound on unallocated bytes left in the arena.
+    /// Ignores free-list reuse (those spans are scattered, not one
+    /// contiguous number), so this can under-report headroom but never
+    /// over-report it.
+    pub fn remaining_capacity(&self) -> usize {
+        let bump = unsafe { *self.bump.get() };
+        ARENA_SIZE.saturating_sub(bump)
+    }
+}
+
+unsafe impl GlobalAlloc for BumpFallbackAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Every block must be big/aligned enough to later hold a `FreeSpan`,
+        // since we don't know at alloc time whether it'll be freed via the
+        // frontier (no write) or the free list (writes a `FreeSpan` in place).
+        let align = layout.align().max(MIN_BLOCK_ALIGN);
+        let size = layout.size().max(MIN_BLOCK_SIZE);
+
+        let buckets = &mut *self.buckets.get();
+        for b in bucket_for(size)..NUM_BUCKETS {
+            let mut slot = &mut buckets[b];
+            while let Some(span) = slot.as_mut() {
+                let addr = span as *mut FreeSpan as usize;
+                if span.size >= size && addr % align == 0 {
+                    *slot = span.next;
+                    return span as *mut FreeSpan as *mut u8;
+                }
+                slot = &mut span.next;
+            }
+        }
+
+        // otherwise bump a fresh span off the arena
+        let base = self.arena.get() as *mut u8;
+        let bump = &mut *self.bump.get();
+        // align the absolute address, not just the offset: the arena itself
+        // is only byte-aligned
+        let base_addr = base as usize;
+        let start = ((base_addr + *bump + align - 1) & !(align - 1)) - base_addr;
+        let end = match start.checked_add(size) {
+            Some(end) if end <= ARENA_SIZE => end,
+            _ => return ptr::null_mut(),
+        };
+        *bump = end;
+        base.add(start)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let size = layout.size().max(MIN_BLOCK_SIZE);
+        let base = self.arena.get() as *mut u8;
+        let bump = &mut *self.bump.get();
+
+        let offset = ptr.offset_from(base) as usize;
+        if offset + size == *bump {
+            // freed span sits right at the frontier: coalesce by retracting
+            // the bump pointer instead of free-listing it.
+            *bump = offset;
+            return;
+        }
+
+        // `alloc` only ever hands out blocks aligned to at least
+        // `MIN_BLOCK_ALIGN` and sized to at least `MIN_BLOCK_SIZE`, so this
+        // write is always in-bounds and properly aligned.
+        let buckets = &mut *self.buckets.get();
+        let bucket = bucket_for(size);
+        let span = ptr as *mut FreeSpan;
+        (*span).size = size;
+        (*span).next = buckets[bucket];
+        buckets[bucket] = span;
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: BumpFallbackAllocator = BumpFallbackAllocator::new();
+
+/// `ALLOCATOR`'s own headroom. `GlobalAlloc::alloc` returning null is not a
+/// recoverable error the way a `Result` would be: `alloc`'s own machinery
+/// treats it as OOM and aborts the whole process, not just the allocation
+/// that triggered it. Callers that can reasonably expect to approach this
+/// arena's capacity (see `OrderBook::add_order`) should check headroom here
+/// first and fail on their own terms instead of letting that happen.
+pub(crate) fn remaining_capacity() -> usize {
+    ALLOCATOR.remaining_capacity()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_adjacent_allocations_survive_a_free() {
+        let alloc = BumpFallbackAllocator::new();
+        unsafe {
+            let layout = Layout::from_size_align(8, 8).unwrap();
+            let a = alloc.alloc(layout);
+            let b = alloc.alloc(layout);
+            assert!(!a.is_null() && !b.is_null());
+
+            core::ptr::write_bytes(b, 0xAB, 8);
+            alloc.dealloc(a, layout);
+
+            let b_bytes = core::slice::from_raw_parts(b, 8);
+            assert_eq!(b_bytes, &[0xAB; 8], "freeing `a` must not corrupt `b`");
+        }
+    }
+
+    #[test]
+    fn reuse_respects_requested_alignment() {
+        let alloc = BumpFallbackAllocator::new();
+        unsafe {
+            let small = Layout::from_size_align(1, 1).unwrap();
+            let aligned = Layout::from_size_align(MIN_BLOCK_SIZE, 32).unwrap();
+
+            let p = alloc.alloc(small);
+            assert!(!p.is_null());
+            alloc.dealloc(p, small);
+
+            // A freed, possibly under-aligned span must never be handed back
+            // for a request that needs stricter alignment.
+            let q = alloc.alloc(aligned);
+            assert!(!q.is_null());
+            assert_eq!(q as usize % 32, 0);
+        }
+    }
+
+    #[test]
+    fn remaining_capacity_tracks_the_bump_frontier() {
+        let alloc = BumpFallbackAllocator::new();
+        assert_eq!(alloc.remaining_capacity(), ARENA_SIZE);
+        unsafe {
+            let layout = Layout::from_size_align(64, 8).unwrap();
+            alloc.alloc(layout);
+        }
+        assert_eq!(alloc.remaining_capacity(), ARENA_SIZE - 64);
+    }
+}
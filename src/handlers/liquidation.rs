@@ -0,0 +1,259 @@
+//! Dutch-auction liquidation for under-collateralized borrows: once a
+//! borrow's effective VTL breaches a maintenance threshold, its collateral is
+//! put up for auction at a decaying ask price until some bid clears it.
+
+use crate::types::order::{Order, OrderStatus, SmallStr};
+use crate::types::rational::{err, ErrorString, Rational};
+
+/// A bid submitted against a running auction.
+#[derive(Clone, Debug)]
+pub struct Bid {
+    pub bidder: SmallStr,
+    pub price: Rational,
+    pub amount: u128,
+}
+
+/// The result of a bid that cleared the current ask price.
+#[derive(Clone, Debug)]
+pub struct Settlement {
+    pub bidder: SmallStr,
+    pub filled_amount: u128,
+    pub collateral: u128,
+}
+
+/// A running Dutch auction over one borrow's collateral. The ask price
+/// starts at `start_price` and decays linearly to `floor_price` over
+/// `duration` ticks: `price(t) = start - (start - floor) * t / duration`.
+#[derive(Clone, Debug)]
+pub struct DutchAuction {
+    pub borrow_id: SmallStr,
+    pub start_price: Rational,
+    pub floor_price: Rational,
+    pub duration: u32,
+    pub collateral: u128,
+    pub amount: u128,
+}
+
+impl DutchAuction {
+    /// Open an auction selling `borrow`'s collateral, starting at `start_price`
+    /// (expected to sit at a premium above the reference price) and decaying
+    /// to `floor_price` over `duration` ticks.
+    pub fn start_auction(
+        borrow: &Order,
+        start_price: Rational,
+        floor_price: Rational,
+        duration: u32,
+    ) -> Result<Self, ErrorString> {
+        if duration == 0 {
+            return Err(err("auction duration must be positive"));
+        }
+        if floor_price > start_price {
+            return Err(err("floor_price must not exceed start_price"));
+        }
+        Ok(DutchAuction {
+            borrow_id: borrow.order_id.clone(),
+            start_price,
+            floor_price,
+            duration,
+            collateral: borrow.collateral,
+            amount: borrow.remaining_amount,
+        })
+    }
+
+    /// The ask price at `tick`, clamped to `floor_price` once `tick >= duration`.
+    pub fn current_price(&self, tick: u32) -> Result<Rational, ErrorString> {
+        let tick = tick.min(self.duration);
+        let drop = self.start_price.checked_sub(&self.floor_price)?;
+        let t = Rational::new(tick as i64, 1)?;
+        let duration = Rational::new(self.duration as i64, 1)?;
+        let decayed = drop.checked_mul(&t)?.checked_div(&duration)?;
+        self.start_price.checked_sub(&decayed)
+    }
+
+    /// Settle `bid` against the ask price at `tick`. If the bid clears
+    /// (`bid.price >= current_price(tick)`), `borrow.remaining_amount` is
+    /// decremented by the filled amount and a proportional share of
+    /// `self.collateral` is handed over; `borrow` is only marked `EXPIRED`
+    /// once the fill exhausts the full auctioned amount, otherwise it's
+    /// `PARTIALLY_FILLED` (same partition discipline as `matching::fill`).
+    /// Returns `None` if the bid doesn't clear or there's nothing left to fill.
+    ///
+    /// Errors if `borrow` isn't the order this auction was opened against —
+    /// `self.collateral`/`self.amount` are this auction's own, and settling
+    /// them against a mismatched order would mutate and report against the
+    /// wrong borrow entirely.
+    pub fn settle(
+        &self,
+        tick: u32,
+        bid: &Bid,
+        borrow: &mut Order,
+    ) -> Result<Option<Settlement>, ErrorString> {
+        if borrow.order_id != self.borrow_id {
+            return Err(err("borrow does not match auction"));
+        }
+        let price = self.current_price(tick)?;
+        if bid.price < price {
+            return Ok(None);
+        }
+        let filled_amount = bid.amount.min(borrow.remaining_amount);
+        if filled_amount == 0 {
+            return Ok(None);
+        }
+        let collateral = self.proportional_collateral(filled_amount)?;
+
+        let new_remaining = borrow.remaining_amount - filled_amount;
+        borrow.remaining_amount = new_remaining;
+        borrow.status = if new_remaining == 0 {
+            OrderStatus::EXPIRED
+        } else {
+            OrderStatus::PARTIALLY_FILLED
+        };
+
+        Ok(Some(Settlement {
+            bidder: bid.bidder.clone(),
+            filled_amount,
+            collateral,
+        }))
+    }
+
+    /// `self.collateral * filled_amount / self.amount`: the share of
+    /// collateral proportional to how much of the originally auctioned
+    /// `amount` this fill covers.
+    fn proportional_collateral(&self, filled_amount: u128) -> Result<u128, ErrorString> {
+        if self.amount == 0 {
+            return Ok(0);
+        }
+        self.collateral
+            .checked_mul(filled_amount)
+            .map(|scaled| scaled / self.amount)
+            .ok_or_else(|| err("overflow computing proportional collateral"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::interval::Interval;
+    use crate::types::order::OrderType;
+    use crate::types::rational::Rational as Rat;
+    use core::convert::TryFrom;
+
+    fn borrow_order(amount: u128, collateral: u128) -> Order {
+        Order::new(
+            "borrow-1",
+            OrderType::BORROW,
+            "USDC",
+            collateral,
+            amount,
+            Interval::<Rat>::from_strs("1.0", "2.0").unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn small_bid_only_claims_proportional_collateral() {
+        let borrow = borrow_order(1_000_000, 1_000_000);
+        let auction = DutchAuction::start_auction(
+            &borrow,
+            Rational::new(2, 1).unwrap(),
+            Rational::new(1, 1).unwrap(),
+            10,
+        )
+        .unwrap();
+        let mut borrow = borrow;
+        let bid = Bid {
+            bidder: SmallStr::try_from("bidder").unwrap(),
+            price: Rational::new(2, 1).unwrap(),
+            amount: 1,
+        };
+
+        let settlement = auction.settle(0, &bid, &mut borrow).unwrap().unwrap();
+
+        assert_eq!(settlement.filled_amount, 1);
+        assert_eq!(settlement.collateral, 1);
+        assert_eq!(borrow.remaining_amount, 999_999);
+        assert_eq!(borrow.status, OrderStatus::PARTIALLY_FILLED);
+    }
+
+    #[test]
+    fn full_bid_exhausts_amount_and_expires_borrow() {
+        let borrow = borrow_order(1_000_000, 1_000_000);
+        let auction = DutchAuction::start_auction(
+            &borrow,
+            Rational::new(2, 1).unwrap(),
+            Rational::new(1, 1).unwrap(),
+            10,
+        )
+        .unwrap();
+        let mut borrow = borrow;
+        let bid = Bid {
+            bidder: SmallStr::try_from("bidder").unwrap(),
+            price: Rational::new(2, 1).unwrap(),
+            amount: 1_000_000,
+        };
+
+        let settlement = auction.settle(0, &bid, &mut borrow).unwrap().unwrap();
+
+        assert_eq!(settlement.filled_amount, 1_000_000);
+        assert_eq!(settlement.collateral, 1_000_000);
+        assert_eq!(borrow.remaining_amount, 0);
+        assert_eq!(borrow.status, OrderStatus::EXPIRED);
+    }
+
+    #[test]
+    fn settle_rejects_a_borrow_that_does_not_match_the_auction() {
+        let borrow = borrow_order(1_000_000, 1_000_000);
+        let auction = DutchAuction::start_auction(
+            &borrow,
+            Rational::new(2, 1).unwrap(),
+            Rational::new(1, 1).unwrap(),
+            10,
+        )
+        .unwrap();
+        let mut other = Order::new(
+            "borrow-2",
+            OrderType::BORROW,
+            "USDC",
+            1_000_000,
+            1_000_000,
+            Interval::<Rat>::from_strs("1.0", "2.0").unwrap(),
+        )
+        .unwrap();
+        let bid = Bid {
+            bidder: SmallStr::try_from("bidder").unwrap(),
+            price: Rational::new(2, 1).unwrap(),
+            amount: 1,
+        };
+
+        assert!(auction.settle(0, &bid, &mut other).is_err());
+    }
+
+    #[test]
+    fn price_decays_linearly_and_clamps_at_floor() {
+        let borrow = borrow_order(100, 100);
+        let auction = DutchAuction::start_auction(
+            &borrow,
+            Rational::new(10, 1).unwrap(),
+            Rational::new(0, 1).unwrap(),
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(
+            auction.current_price(0).unwrap(),
+            Rational::new(10, 1).unwrap()
+        );
+        assert_eq!(
+            auction.current_price(5).unwrap(),
+            Rational::new(5, 1).unwrap()
+        );
+        assert_eq!(
+            auction.current_price(10).unwrap(),
+            Rational::new(0, 1).unwrap()
+        );
+        assert_eq!(
+            auction.current_price(50).unwrap(),
+            Rational::new(0, 1).unwrap()
+        );
+    }
+}
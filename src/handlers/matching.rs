@@ -1,11 +1,78 @@
 use core::cmp::Ordering;
 
-use crate::types::order::{Order, OrderBook, OrderStatus};
+use crate::types::order::{Order, OrderBook, OrderKind, OrderStatus, SmallStr};
+use crate::types::rational::{err, ErrorString, Rational};
 
-/// Find the first open borrow order whose VTL range overlaps with the lend_order
-pub fn match_lend<'a>(borrow_orderbook: &'a OrderBook, lend_order: &'a Order) -> Option<&'a Order> {
-    let lend_min = &lend_order.vtl_range.min;
-    let lend_max = &lend_order.vtl_range.max;
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+/// The live price feed a `Pegged` order's effective VTL band is derived from.
+pub type OraclePrice = Rational;
+
+/// One borrow order's share of a `fill` sweep.
+#[derive(Clone, Debug)]
+pub struct Allocation {
+    pub borrow_id: SmallStr,
+    pub filled_amount: u128,
+}
+
+/// `fill`'s working set of candidate borrow ids / allocations. Bounded to
+/// `MAX_ORDERS` (the book's own capacity — a sweep can never touch more
+/// borrows than exist in the book) when `alloc` is off, or growable when
+/// it's on, matching `OrderBook`'s own backing store from chunk0-3.
+#[cfg(not(feature = "alloc"))]
+type FillIds = heapless::Vec<SmallStr, { crate::types::order::MAX_ORDERS }>;
+#[cfg(feature = "alloc")]
+type FillIds = alloc::vec::Vec<SmallStr>;
+
+#[cfg(not(feature = "alloc"))]
+type Allocations = heapless::Vec<Allocation, { crate::types::order::MAX_ORDERS }>;
+#[cfg(feature = "alloc")]
+type Allocations = alloc::vec::Vec<Allocation>;
+
+#[cfg(not(feature = "alloc"))]
+fn push_id(ids: &mut FillIds, id: SmallStr) -> Result<(), ErrorString> {
+    ids.push(id)
+        .map_err(|_| err("fill overflowed the order book's own capacity"))
+}
+#[cfg(feature = "alloc")]
+fn push_id(ids: &mut FillIds, id: SmallStr) -> Result<(), ErrorString> {
+    ids.push(id);
+    Ok(())
+}
+
+#[cfg(not(feature = "alloc"))]
+fn push_allocation(
+    allocations: &mut Allocations,
+    allocation: Allocation,
+) -> Result<(), ErrorString> {
+    allocations
+        .push(allocation)
+        .map_err(|_| err("fill overflowed the order book's own capacity"))
+}
+#[cfg(feature = "alloc")]
+fn push_allocation(
+    allocations: &mut Allocations,
+    allocation: Allocation,
+) -> Result<(), ErrorString> {
+    allocations.push(allocation);
+    Ok(())
+}
+
+/// Find the first open borrow order whose VTL range overlaps with the lend_order.
+///
+/// Fixed orders are narrowed with a binary search over the (unchanged) sorted
+/// `orders_by_vtl`; pegged orders move with the oracle, so `orders_by_vtl`
+/// can't be trusted to bracket their effective bounds and they get a linear
+/// fallback scan instead.
+pub fn match_lend<'a>(
+    borrow_orderbook: &'a OrderBook,
+    lend_order: &'a Order,
+    oracle_price: &OraclePrice,
+) -> Result<Option<&'a Order>, ErrorString> {
+    let lend_range = lend_order.effective_vtl_range(oracle_price)?;
+    let lend_min = &lend_range.min;
+    let lend_max = &lend_range.max;
 
     // binary search for first entry with min > lend_min
     let mut lo = 0;
@@ -26,27 +93,46 @@ pub fn match_lend<'a>(borrow_orderbook: &'a OrderBook, lend_order: &'a Order) ->
     }
     let idx = lo;
 
-    // scan for overlap
+    // scan for overlap among fixed orders only
     for o in &borrow_orderbook.orders_by_vtl[idx..] {
-        if o.status != OrderStatus::OPEN {
+        if o.status != OrderStatus::OPEN || matches!(o.order_kind, OrderKind::Pegged) {
             continue;
         }
         let lower = o.vtl_range.min.clone().max(lend_min.clone());
         let upper = o.vtl_range.max.clone().min(lend_max.clone());
         if lower <= upper {
-            return Some(o);
+            return Ok(Some(o));
+        }
+    }
+
+    // fall back to a linear overlap scan for the pegged subset
+    for o in &borrow_orderbook.orders_by_vtl {
+        if o.status != OrderStatus::OPEN || !matches!(o.order_kind, OrderKind::Pegged) {
+            continue;
+        }
+        let effective = o.effective_vtl_range(oracle_price)?;
+        let lower = effective.min.clone().max(lend_min.clone());
+        let upper = effective.max.clone().min(lend_max.clone());
+        if lower <= upper {
+            return Ok(Some(o));
         }
     }
-    None
+
+    Ok(None)
 }
 
-/// Find the best lend order whose VTL range overlaps with the borrow_order
+/// Find the best lend order whose VTL range overlaps with the borrow_order.
+///
+/// See `match_lend` for why fixed orders use the binary search and pegged
+/// orders fall back to a linear scan.
 pub fn match_borrow<'a>(
     lend_orderbook: &'a OrderBook,
     borrow_order: &'a Order,
-) -> Option<&'a Order> {
-    let borrow_min = &borrow_order.vtl_range.min;
-    let borrow_max = &borrow_order.vtl_range.max;
+    oracle_price: &OraclePrice,
+) -> Result<Option<&'a Order>, ErrorString> {
+    let borrow_range = borrow_order.effective_vtl_range(oracle_price)?;
+    let borrow_min = &borrow_range.min;
+    let borrow_max = &borrow_range.max;
 
     // find insertion point = first lend.min > borrow_min
     let idx = lend_orderbook
@@ -60,21 +146,287 @@ pub fn match_borrow<'a>(
         })
         .unwrap_or_else(|i| i);
 
-    // if idx == 0, no lend.min <= borrow_min
-    if idx == 0 {
-        return None;
+    // if idx == 0, no fixed lend.min <= borrow_min
+    if idx > 0 {
+        let o = &lend_orderbook.orders_by_vtl[idx - 1];
+        if o.status == OrderStatus::OPEN && !matches!(o.order_kind, OrderKind::Pegged) {
+            let lower = o.vtl_range.min.clone().max(borrow_min.clone());
+            let upper = o.vtl_range.max.clone().min(borrow_max.clone());
+            if lower <= upper {
+                return Ok(Some(o));
+            }
+        }
     }
 
-    let o = &lend_orderbook.orders_by_vtl[idx - 1];
-    if o.status != OrderStatus::OPEN {
-        return None;
+    // fall back to a linear overlap scan for the pegged subset
+    for o in &lend_orderbook.orders_by_vtl {
+        if o.status != OrderStatus::OPEN || !matches!(o.order_kind, OrderKind::Pegged) {
+            continue;
+        }
+        let effective = o.effective_vtl_range(oracle_price)?;
+        let lower = effective.min.clone().max(borrow_min.clone());
+        let upper = effective.max.clone().min(borrow_max.clone());
+        if lower <= upper {
+            return Ok(Some(o));
+        }
     }
 
-    let lower = o.vtl_range.min.clone().max(borrow_min.clone());
-    let upper = o.vtl_range.max.clone().min(borrow_max.clone());
-    if lower > upper {
-        return None;
+    Ok(None)
+}
+
+/// Sweep `lend_order` across every open/partially-filled borrow that
+/// overlaps it in `borrow_orderbook` (in VTL order, fixed then pegged — see
+/// `match_lend`), allocating `remaining_amount` greedily until the lend side
+/// is exhausted or no overlap remains. Each touched borrow's
+/// `remaining_amount`/`status` is updated in place, as is `lend_order`'s.
+pub fn fill(
+    borrow_orderbook: &mut OrderBook,
+    lend_order: &mut Order,
+    oracle_price: &OraclePrice,
+) -> Result<Allocations, ErrorString> {
+    let lend_range = lend_order.effective_vtl_range(oracle_price)?;
+    let lend_min = &lend_range.min;
+    let lend_max = &lend_range.max;
+
+    // collect overlapping borrow ids in VTL order before mutating anything,
+    // same split as match_lend: binary search narrows the fixed subset (to
+    // entries with min <= lend_max, the only ones that can overlap), the
+    // pegged subset gets a linear fallback scan.
+    let mut candidate_ids: FillIds = Default::default();
+
+    let mut lo = 0;
+    let mut hi = borrow_orderbook.orders_by_vtl.len();
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        let probe = &borrow_orderbook.orders_by_vtl[mid];
+        let cmp = if &probe.vtl_range.min <= lend_max {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+        if cmp == Ordering::Less {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
     }
+    for o in &borrow_orderbook.orders_by_vtl[..lo] {
+        if matches!(o.order_kind, OrderKind::Pegged) {
+            continue;
+        }
+        if o.status != OrderStatus::OPEN && o.status != OrderStatus::PARTIALLY_FILLED {
+            continue;
+        }
+        let lower = o.vtl_range.min.clone().max(lend_min.clone());
+        let upper = o.vtl_range.max.clone().min(lend_max.clone());
+        if lower <= upper {
+            push_id(&mut candidate_ids, o.order_id.clone())?;
+        }
+    }
+    for o in &borrow_orderbook.orders_by_vtl {
+        if !matches!(o.order_kind, OrderKind::Pegged) {
+            continue;
+        }
+        if o.status != OrderStatus::OPEN && o.status != OrderStatus::PARTIALLY_FILLED {
+            continue;
+        }
+        let effective = o.effective_vtl_range(oracle_price)?;
+        let lower = effective.min.clone().max(lend_min.clone());
+        let upper = effective.max.clone().min(lend_max.clone());
+        if lower <= upper {
+            push_id(&mut candidate_ids, o.order_id.clone())?;
+        }
+    }
+
+    // the sweep can never allocate more than this, whatever the book looks like
+    let total_candidate_remaining: u128 = candidate_ids
+        .iter()
+        .filter_map(|id| borrow_orderbook.get_order_by_id(id))
+        .map(|o| o.remaining_amount)
+        .sum();
+    let expected_total = lend_order.remaining_amount.min(total_candidate_remaining);
+
+    let mut allocations: Allocations = Default::default();
+    let mut remaining = lend_order.remaining_amount;
+
+    for id in &candidate_ids {
+        if remaining == 0 {
+            break;
+        }
+        let borrow_remaining = match borrow_orderbook.get_order_by_id(id) {
+            Some(o) => o.remaining_amount,
+            None => continue,
+        };
+        let take = remaining.min(borrow_remaining);
+        if take == 0 {
+            continue;
+        }
+        let new_remaining = borrow_remaining - take;
+        let new_status = if new_remaining == 0 {
+            OrderStatus::FILLED
+        } else {
+            OrderStatus::PARTIALLY_FILLED
+        };
+        borrow_orderbook.set_remaining(id, new_remaining, new_status);
+        remaining -= take;
+        push_allocation(
+            &mut allocations,
+            Allocation {
+                borrow_id: id.clone(),
+                filled_amount: take,
+            },
+        )?;
+    }
+
+    let total_filled: u128 = allocations.iter().map(|a| a.filled_amount).sum();
+    if total_filled != expected_total {
+        return Err(err("fill allocated more or less than the overlap admits"));
+    }
+
+    lend_order.remaining_amount = remaining;
+    lend_order.status = if remaining == 0 {
+        OrderStatus::FILLED
+    } else if remaining < lend_order.amount {
+        OrderStatus::PARTIALLY_FILLED
+    } else {
+        OrderStatus::OPEN
+    };
+
+    Ok(allocations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::interval::Interval;
+    use crate::types::order::OrderType;
+    use core::convert::TryFrom;
+
+    fn borrow(id: &str, amount: u128, min: &str, max: &str) -> Order {
+        Order::new(
+            id,
+            OrderType::BORROW,
+            "USDC",
+            amount,
+            amount,
+            Interval::<Rational>::from_strs(min, max).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn lend(amount: u128, min: &str, max: &str) -> Order {
+        Order::new(
+            "lend-1",
+            OrderType::LEND,
+            "USDC",
+            amount,
+            amount,
+            Interval::<Rational>::from_strs(min, max).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn pegged(
+        id: &str,
+        order_type: OrderType,
+        amount: u128,
+        peg_min: &str,
+        peg_max: &str,
+    ) -> Order {
+        Order::new_pegged(
+            id,
+            order_type,
+            "USDC",
+            amount,
+            amount,
+            Interval::<Rational>::from_strs("1.0", "2.0").unwrap(),
+            Rational::from_decimal_str(peg_min).unwrap(),
+            Rational::from_decimal_str(peg_max).unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn fill_sweeps_across_multiple_borrows_with_partial_fill() {
+        let mut book = OrderBook::new();
+        book.add_order(borrow("b1", 40, "1.0", "2.0"));
+        book.add_order(borrow("b2", 40, "1.0", "2.0"));
+        book.add_order(borrow("b3", 40, "1.0", "2.0"));
+        let oracle_price = Rational::new(15, 10).unwrap();
+
+        let mut lend_order = lend(100, "1.0", "2.0");
+        let allocations = fill(&mut book, &mut lend_order, &oracle_price).unwrap();
 
-    Some(o)
+        let total_filled: u128 = allocations.iter().map(|a| a.filled_amount).sum();
+        assert_eq!(total_filled, 100);
+        assert_eq!(lend_order.remaining_amount, 0);
+        assert_eq!(lend_order.status, OrderStatus::FILLED);
+
+        // first two borrows fully drained, the third only partially
+        let b3 = book
+            .get_order_by_id(&SmallStr::try_from("b3").unwrap())
+            .unwrap();
+        assert_eq!(b3.remaining_amount, 20);
+        assert_eq!(b3.status, OrderStatus::PARTIALLY_FILLED);
+    }
+
+    #[test]
+    fn fill_never_allocates_more_than_the_book_can_cover() {
+        let mut book = OrderBook::new();
+        book.add_order(borrow("b1", 10, "1.0", "2.0"));
+        let oracle_price = Rational::new(15, 10).unwrap();
+
+        let mut lend_order = lend(100, "1.0", "2.0");
+        let allocations = fill(&mut book, &mut lend_order, &oracle_price).unwrap();
+
+        let total_filled: u128 = allocations.iter().map(|a| a.filled_amount).sum();
+        assert_eq!(total_filled, 10);
+        assert_eq!(lend_order.remaining_amount, 90);
+        assert_eq!(lend_order.status, OrderStatus::PARTIALLY_FILLED);
+    }
+
+    #[test]
+    fn match_lend_falls_back_to_a_pegged_borrow_against_a_fixed_lend() {
+        let mut book = OrderBook::new();
+        // the fixed-order scan skips `Pegged` orders whatever their
+        // submission-time vtl_range ([1.0, 2.0], set by the `pegged` helper),
+        // so finding it at all proves the match came from the pegged fallback scan.
+        book.add_order(pegged("peg-borrow", OrderType::BORROW, 50, "-0.1", "0.1"));
+        let oracle_price = Rational::new(11, 10).unwrap(); // 1.1 -> effective [1.0, 1.2]
+
+        let lend_order = lend(50, "1.0", "1.2");
+        let found = match_lend(&book, &lend_order, &oracle_price)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(found.order_id, SmallStr::try_from("peg-borrow").unwrap());
+    }
+
+    #[test]
+    fn match_lend_falls_back_to_a_pegged_borrow_against_a_pegged_lend() {
+        let mut book = OrderBook::new();
+        book.add_order(pegged("peg-borrow", OrderType::BORROW, 50, "-0.1", "0.1"));
+        let oracle_price = Rational::new(11, 10).unwrap(); // 1.1 -> effective [1.0, 1.2]
+
+        let lend_order = pegged("peg-lend", OrderType::LEND, 50, "-0.05", "0.05");
+        let found = match_lend(&book, &lend_order, &oracle_price)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(found.order_id, SmallStr::try_from("peg-borrow").unwrap());
+    }
+
+    #[test]
+    fn match_borrow_falls_back_to_a_pegged_lend_against_a_fixed_borrow() {
+        let mut lend_book = OrderBook::new();
+        lend_book.add_order(pegged("peg-lend", OrderType::LEND, 50, "-0.1", "0.1"));
+        let oracle_price = Rational::new(11, 10).unwrap(); // 1.1 -> effective [1.0, 1.2]
+
+        let borrow_order = borrow("fixed-borrow", 50, "1.0", "1.2");
+        let found = match_borrow(&lend_book, &borrow_order, &oracle_price)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(found.order_id, SmallStr::try_from("peg-lend").unwrap());
+    }
 }
@@ -0,0 +1,151 @@
+//! Utilization-based, two-slope ("kinked") interest-rate model: flat-ish
+//! below `u_kink`, steeper above it, so the matching engine has a principled
+//! rate to attach to a fill instead of just a paired VTL range.
+
+use core::convert::TryFrom;
+
+use crate::types::rational::{err, ErrorString, Rational};
+
+/// Parameters of a kinked borrow-rate curve:
+/// `rate = base + u * slope1` below `u_kink`, and
+/// `rate = base + u_kink * slope1 + (u - u_kink) * slope2` at or above it.
+#[derive(Clone, Debug)]
+pub struct RateModel {
+    pub base: Rational,
+    pub slope1: Rational,
+    pub slope2: Rational,
+    pub u_kink: Rational,
+}
+
+/// The common factor `utilization` divides both amounts by before narrowing
+/// to `i64`: 1 if `max_amount` already fits, otherwise the smallest divisor
+/// that brings it within `i64::MAX`.
+fn scale_factor(max_amount: u128) -> u128 {
+    let limit = i64::MAX as u128;
+    if max_amount <= limit {
+        1
+    } else {
+        // ceiling division, so the scaled amount never lands back above `limit`
+        (max_amount + limit - 1) / limit
+    }
+}
+
+/// `total_borrowed / total_supplied`, or zero if nothing has been supplied.
+///
+/// Both amounts are token quantities (`u128`, 18-decimal-friendly per the
+/// rest of this crate's convention) and routinely exceed `i64::MAX`, so they
+/// are scaled down by a common factor before narrowing to the `i64` that
+/// `Rational` stores — narrowing directly would make `utilization` error on
+/// any realistically sized pool. Scaling both amounts by the same factor
+/// preserves their ratio (up to the scaled-down amounts' own rounding).
+pub fn utilization(total_borrowed: u128, total_supplied: u128) -> Result<Rational, ErrorString> {
+    if total_supplied == 0 {
+        return Ok(Rational::zero());
+    }
+    let scale = scale_factor(total_borrowed.max(total_supplied));
+    let borrowed = Rational::new(
+        i64::try_from(total_borrowed / scale)
+            .map_err(|_| err("total_borrowed exceeds i64 range"))?,
+        1,
+    )?;
+    let supplied = Rational::new(
+        i64::try_from(total_supplied / scale)
+            .map_err(|_| err("total_supplied exceeds i64 range"))?,
+        1,
+    )?;
+    borrowed.checked_div(&supplied)
+}
+
+impl RateModel {
+    /// The borrow rate at a given utilization, per the two-slope curve.
+    pub fn borrow_rate(&self, utilization: &Rational) -> Result<Rational, ErrorString> {
+        if utilization <= &self.u_kink {
+            let slope_component = utilization.checked_mul(&self.slope1)?;
+            self.base.checked_add(&slope_component)
+        } else {
+            let below_kink = self.u_kink.checked_mul(&self.slope1)?;
+            let excess = utilization.checked_sub(&self.u_kink)?;
+            let above_kink = excess.checked_mul(&self.slope2)?;
+            self.base.checked_add(&below_kink)?.checked_add(&above_kink)
+        }
+    }
+
+    /// `borrow_rate(utilization) * utilization * (1 - reserve_factor)`.
+    pub fn supply_rate(
+        &self,
+        utilization: &Rational,
+        reserve_factor: &Rational,
+    ) -> Result<Rational, ErrorString> {
+        let borrow = self.borrow_rate(utilization)?;
+        let retained = Rational::one().checked_sub(reserve_factor)?;
+        borrow.checked_mul(utilization)?.checked_mul(&retained)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utilization_is_zero_with_no_supply() {
+        assert_eq!(utilization(0, 0).unwrap(), Rational::zero());
+        assert_eq!(utilization(5, 0).unwrap(), Rational::zero());
+    }
+
+    #[test]
+    fn utilization_scales_down_amounts_past_i64_range() {
+        let limit = i64::MAX as u128;
+        let total_supplied = limit + 1; // exceeds i64::MAX, forces scaling
+        let total_borrowed = (limit + 1) / 2;
+
+        let u = utilization(total_borrowed, total_supplied).unwrap();
+
+        assert_eq!(u, Rational::new(1, 2).unwrap());
+    }
+
+    fn model() -> RateModel {
+        RateModel {
+            base: Rational::new(2, 100).unwrap(),     // 2%
+            slope1: Rational::new(10, 100).unwrap(),  // 10%
+            slope2: Rational::new(100, 100).unwrap(), // 100%
+            u_kink: Rational::new(80, 100).unwrap(),  // 80%
+        }
+    }
+
+    #[test]
+    fn borrow_rate_below_kink_uses_slope1_only() {
+        let m = model();
+        let u = Rational::new(40, 100).unwrap(); // half of u_kink
+        let rate = m.borrow_rate(&u).unwrap();
+        // base + u * slope1 = 0.02 + 0.40 * 0.10 = 0.06
+        assert_eq!(rate, Rational::new(6, 100).unwrap());
+    }
+
+    #[test]
+    fn borrow_rate_at_kink_matches_either_formula() {
+        let m = model();
+        let rate = m.borrow_rate(&m.u_kink).unwrap();
+        // base + u_kink * slope1 = 0.02 + 0.80 * 0.10 = 0.10
+        assert_eq!(rate, Rational::new(10, 100).unwrap());
+    }
+
+    #[test]
+    fn borrow_rate_above_kink_adds_slope2_on_the_excess() {
+        let m = model();
+        let u = Rational::new(90, 100).unwrap();
+        let rate = m.borrow_rate(&u).unwrap();
+        // base + u_kink * slope1 + (u - u_kink) * slope2
+        // = 0.02 + 0.08 + 0.10 * 1.00 = 0.20
+        assert_eq!(rate, Rational::new(20, 100).unwrap());
+    }
+
+    #[test]
+    fn supply_rate_nets_out_the_reserve_factor() {
+        let m = model();
+        let u = Rational::new(40, 100).unwrap();
+        let reserve_factor = Rational::new(10, 100).unwrap();
+        let rate = m.supply_rate(&u, &reserve_factor).unwrap();
+        // borrow_rate(u) * u * (1 - reserve_factor) = 0.06 * 0.40 * 0.90 = 0.0216
+        assert_eq!(rate, Rational::new(216, 10_000).unwrap());
+    }
+}
@@ -1,21 +1,55 @@
 //! Order book implementation reworked for `#![no_std]` / allocator‑free setups.
-//! Strings → `heapless::String`, Vecs → `heapless::Vec`.
+//! Strings stay `heapless::String` either way; `orders_by_vtl`/`orders_by_id`
+//! are `heapless` containers by default, or growable `alloc` containers (see
+//! `alloc_support`) behind the `alloc` feature — same public API either way.
 
 use core::cmp::Ordering;
 use core::convert::TryFrom;
 use core::fmt;
 
-use heapless::{FnvIndexMap, String as HString, Vec as HVec};
+use heapless::String as HString;
 
 use crate::types::interval::Interval;
 use crate::types::rational::{err, ErrorString, Rational};
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+/// `orders_by_vtl`'s backing store: a fixed-capacity `heapless::Vec` by
+/// default, or a growable `alloc::vec::Vec` with the `alloc` feature. Either
+/// way `OrderBook`'s public API is identical.
+#[cfg(not(feature = "alloc"))]
+type OrderVec = heapless::Vec<Order, MAX_ORDERS>;
+#[cfg(feature = "alloc")]
+type OrderVec = alloc::vec::Vec<Order>;
+
+/// `orders_by_id`'s backing store: a fixed-capacity `FnvIndexMap` by default,
+/// or a growable `BTreeMap` with the `alloc` feature.
+#[cfg(not(feature = "alloc"))]
+type OrderMap = heapless::FnvIndexMap<SmallStr, Order, MAX_KEYS>;
+#[cfg(feature = "alloc")]
+type OrderMap = alloc::collections::BTreeMap<SmallStr, Order>;
+
 /// Tunables ----------------------------------------------------------------
 /// Adjust at will; grow if you expect bigger order books or identifiers.
-const MAX_ORDERS: usize = 32; // capacity of the per‑book Vec
+/// Only used by the fixed-capacity containers; the `alloc` feature grows
+/// well past these before `add_order` itself caps it (see
+/// `ALLOC_SAFETY_MARGIN`), rather than failing at a fixed order count.
+#[cfg(not(feature = "alloc"))]
+pub(crate) const MAX_ORDERS: usize = 32; // capacity of the per‑book Vec
+#[cfg(not(feature = "alloc"))]
 const MAX_KEYS: usize = 16; // capacity of the ID → Order map
 const STR_CAP: usize = 32; // capacity for IDs, asset symbols, …
 
+/// Headroom `add_order` insists on in the arena before growing the `alloc`
+/// containers, so a book nearing the arena's capacity panics on its own
+/// terms (same message as the `heapless` build's cap) instead of leaving it
+/// to `BumpFallbackAllocator` to return null and abort the process. Sized
+/// generously above one order's worst-case growth (a fresh `Order` clone in
+/// both containers, plus a `Vec`/`BTreeMap` reallocation).
+#[cfg(feature = "alloc")]
+const ALLOC_SAFETY_MARGIN: usize = 4 * 1024;
+
 /// Convenience alias for fixed‑capacity heapless strings.
 pub type SmallStr = HString<STR_CAP>;
 
@@ -36,6 +70,25 @@ pub enum OrderStatus {
     EXPIRED,
 }
 
+/// Whether an order's VTL band is a static range or tracks a live oracle price.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderKind {
+    /// `vtl_range` is the order's effective band for its whole lifetime.
+    Fixed,
+    /// The effective band is recomputed at match time from `PegDescriptor`
+    /// and the current oracle price; `vtl_range` only holds the bounds at
+    /// submission time and goes stale as the oracle moves.
+    Pegged,
+}
+
+/// Offsets applied to the oracle price to derive a pegged order's effective
+/// `vtl_range` at match time: `[oracle + peg_offset_min, oracle + peg_offset_max]`.
+#[derive(Clone, Debug)]
+pub struct PegDescriptor {
+    pub peg_offset_min: Rational,
+    pub peg_offset_max: Rational,
+}
+
 #[derive(Clone, Debug)]
 pub struct Order {
     pub order_id: SmallStr,
@@ -46,6 +99,8 @@ pub struct Order {
     pub amount: u128,
     pub remaining_amount: u128,
     pub vtl_range: Interval<Rational>,
+    pub order_kind: OrderKind,
+    pub peg: Option<PegDescriptor>,
 }
 
 impl fmt::Display for Order {
@@ -67,8 +122,8 @@ impl fmt::Display for Order {
 
 #[derive(Debug)]
 pub struct OrderBook {
-    pub orders_by_vtl: HVec<Order, MAX_ORDERS>,
-    pub orders_by_id: FnvIndexMap<SmallStr, Order, MAX_KEYS>,
+    pub orders_by_vtl: OrderVec,
+    pub orders_by_id: OrderMap,
 }
 
 impl fmt::Display for OrderBook {
@@ -127,15 +182,63 @@ impl Order {
             amount,
             remaining_amount: amount,
             vtl_range,
+            order_kind: OrderKind::Fixed,
+            peg: None,
         })
     }
+
+    /// Construct an oracle-pegged order. `vtl_range` is the initial band at
+    /// submission time; from then on `effective_vtl_range` recomputes it from
+    /// the live oracle price instead.
+    pub fn new_pegged(
+        order_id: &str,
+        order_type: OrderType,
+        asset: &str,
+        collateral: u128,
+        amount: u128,
+        vtl_range: Interval<Rational>,
+        peg_offset_min: Rational,
+        peg_offset_max: Rational,
+    ) -> Result<Self, ErrorString> {
+        if peg_offset_min > peg_offset_max {
+            return Err(err("peg_offset_min must be ≤ peg_offset_max"));
+        }
+        let mut order = Self::new(order_id, order_type, asset, collateral, amount, vtl_range)?;
+        order.order_kind = OrderKind::Pegged;
+        order.peg = Some(PegDescriptor {
+            peg_offset_min,
+            peg_offset_max,
+        });
+        Ok(order)
+    }
+
+    /// The VTL band to use for matching right now: `vtl_range` as-is for
+    /// `Fixed` orders, or `[oracle + peg_offset_min, oracle + peg_offset_max]`
+    /// for `Pegged` orders.
+    pub fn effective_vtl_range(
+        &self,
+        oracle_price: &Rational,
+    ) -> Result<Interval<Rational>, ErrorString> {
+        match self.order_kind {
+            OrderKind::Fixed => Ok(self.vtl_range.clone()),
+            OrderKind::Pegged => {
+                let peg = self
+                    .peg
+                    .as_ref()
+                    .ok_or_else(|| err("pegged order missing peg descriptor"))?;
+                let min = oracle_price.checked_add(&peg.peg_offset_min)?;
+                let max = oracle_price.checked_add(&peg.peg_offset_max)?;
+                Interval::new(min, max)
+            }
+        }
+    }
 }
 
 impl OrderBook {
     pub const fn new() -> Self {
         Self {
-            orders_by_vtl: HVec::new(),
-            orders_by_id: FnvIndexMap::new(),
+            orders_by_vtl: OrderVec::new(),
+            orders_by_id: OrderMap::new(),
         }
     }
 
@@ -152,20 +255,40 @@ impl OrderBook {
     }
 
     fn insert_sorted(
-        vec: &mut HVec<Order, MAX_ORDERS>,
+        vec: &mut OrderVec,
         order: Order,
         mut compare: impl FnMut(&Order, &Order) -> Ordering,
     ) {
         let idx = vec
             .binary_search_by(|probe| compare(probe, &order))
             .unwrap_or_else(|e| e);
-        vec.insert(idx, order).ok(); // ignore capacity error (caller ensured room)
+        Self::vec_insert(vec, idx, order);
+    }
+
+    /// Fixed-capacity insert: silently drops the order if the book is full
+    /// (caller is expected to have checked capacity via `add_order`).
+    #[cfg(not(feature = "alloc"))]
+    fn vec_insert(vec: &mut OrderVec, idx: usize, order: Order) {
+        vec.insert(idx, order).ok();
+    }
+
+    /// Growable insert: no fixed order-count ceiling (`add_order` still
+    /// refuses new orders once arena headroom drops below
+    /// `ALLOC_SAFETY_MARGIN`).
+    #[cfg(feature = "alloc")]
+    fn vec_insert(vec: &mut OrderVec, idx: usize, order: Order) {
+        vec.insert(idx, order);
     }
 
     pub fn add_order(&mut self, order: Order) {
+        #[cfg(not(feature = "alloc"))]
         if self.orders_by_vtl.len() == MAX_KEYS || self.orders_by_id.len() == MAX_KEYS {
             panic!("order book full");
         }
+        #[cfg(feature = "alloc")]
+        if crate::alloc_support::remaining_capacity() < ALLOC_SAFETY_MARGIN {
+            panic!("order book full");
+        }
         let order_id = order.order_id.clone();
         let order_for_map = order.clone();
         Self::insert_sorted(&mut self.orders_by_vtl, order, |o1, o2| {
@@ -176,10 +299,109 @@ impl OrderBook {
                 o1.order_id.cmp(&o2.order_id)
             }
         });
-        self.orders_by_id.insert(order_id, order_for_map).ok();
+        Self::map_insert(&mut self.orders_by_id, order_id, order_for_map);
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn map_insert(map: &mut OrderMap, id: SmallStr, order: Order) {
+        map.insert(id, order).ok();
+    }
+
+    #[cfg(feature = "alloc")]
+    fn map_insert(map: &mut OrderMap, id: SmallStr, order: Order) {
+        map.insert(id, order);
     }
 
     pub fn remove_order(&mut self, id: &SmallStr) -> Option<Order> {
         self.orders_by_id.remove(id)
     }
+
+    /// Write `remaining_amount`/`status` into both backing copies of the
+    /// order with this id, since `orders_by_vtl` and `orders_by_id` each hold
+    /// their own clone.
+    pub fn set_remaining(&mut self, id: &SmallStr, remaining_amount: u128, status: OrderStatus) {
+        if let Some(o) = self.orders_by_id.get_mut(id) {
+            o.remaining_amount = remaining_amount;
+            o.status = status.clone();
+        }
+        if let Some(o) = self.orders_by_vtl.iter_mut().find(|o| &o.order_id == id) {
+            o.remaining_amount = remaining_amount;
+            o.status = status;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min: &str, max: &str) -> Interval<Rational> {
+        Interval::<Rational>::from_strs(min, max).unwrap()
+    }
+
+    #[test]
+    fn new_pegged_rejects_an_inverted_offset_range() {
+        let err = Order::new_pegged(
+            "order-1",
+            OrderType::LEND,
+            "USDC",
+            100,
+            100,
+            range("1.0", "2.0"),
+            Rational::new(1, 10).unwrap(),
+            Rational::new(-1, 10).unwrap(),
+        )
+        .unwrap_err();
+        assert!(!err.is_empty());
+    }
+
+    #[test]
+    fn new_pegged_accepts_equal_offsets() {
+        let order = Order::new_pegged(
+            "order-1",
+            OrderType::LEND,
+            "USDC",
+            100,
+            100,
+            range("1.0", "2.0"),
+            Rational::new(1, 10).unwrap(),
+            Rational::new(1, 10).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(order.order_kind, OrderKind::Pegged);
+    }
+
+    #[test]
+    fn effective_vtl_range_is_static_for_fixed_orders() {
+        let order = Order::new(
+            "order-1",
+            OrderType::LEND,
+            "USDC",
+            100,
+            100,
+            range("1.0", "2.0"),
+        )
+        .unwrap();
+        let oracle_price = Rational::new(5, 1).unwrap();
+        let effective = order.effective_vtl_range(&oracle_price).unwrap();
+        assert_eq!(effective, range("1.0", "2.0"));
+    }
+
+    #[test]
+    fn effective_vtl_range_tracks_the_oracle_for_pegged_orders() {
+        let order = Order::new_pegged(
+            "order-1",
+            OrderType::LEND,
+            "USDC",
+            100,
+            100,
+            range("1.0", "2.0"),
+            Rational::new(-1, 10).unwrap(),
+            Rational::new(1, 10).unwrap(),
+        )
+        .unwrap();
+        let oracle_price = Rational::new(15, 10).unwrap(); // 1.5
+        let effective = order.effective_vtl_range(&oracle_price).unwrap();
+        assert_eq!(effective, range("1.4", "1.6"));
+    }
 }
@@ -31,6 +31,29 @@ fn gcd(mut a: i64, mut b: i64) -> i64 {
     a.abs()
 }
 
+/// Greatest‑common‑divisor over the `i128` intermediates used while reducing.
+fn gcd_i128(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        let t = b;
+        b = a % b;
+        a = t;
+    }
+    a.abs()
+}
+
+/// Reduce an `i128` numerator/denominator pair via `gcd` and narrow back to
+/// `i64`, erroring instead of panicking if the reduced value doesn't fit.
+fn from_i128(num: i128, den: i128) -> Result<Rational, ErrorString> {
+    if den == 0 {
+        return Err(err("Denominator cannot be zero"));
+    }
+    let sign: i128 = if den < 0 { -1 } else { 1 };
+    let g = gcd_i128(num, den).max(1);
+    let num = i64::try_from(sign * (num / g)).map_err(|_| err("Overflow computing numerator"))?;
+    let den = i64::try_from(den.abs() / g).map_err(|_| err("Overflow computing denominator"))?;
+    Rational::new(num, den)
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Rational {
     num: i64,
@@ -51,6 +74,24 @@ impl Rational {
         })
     }
 
+    /// The additive identity, `0/1`.
+    pub fn zero() -> Self {
+        Rational { num: 0, den: 1 }
+    }
+
+    /// The multiplicative identity, `1/1`.
+    pub fn one() -> Self {
+        Rational { num: 1, den: 1 }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+
+    pub fn is_sign_positive(&self) -> bool {
+        self.num >= 0
+    }
+
     /// Parse from a decimal string such as "-12.34" or "7".
     pub fn from_decimal_str(s: &str) -> Result<Self, ErrorString> {
         let s = s.trim();
@@ -71,16 +112,35 @@ impl Rational {
 
     /// Checked subtraction that propagates overflow / zero‑denominator issues.
     pub fn checked_sub(&self, rhs: &Rational) -> Result<Rational, ErrorString> {
-        let num = self
-            .num
-            .checked_mul(rhs.den)
-            .and_then(|ad| rhs.num.checked_mul(self.den).map(|cb| ad - cb))
-            .ok_or_else(|| err("Overflow computing numerator"))?;
-        let den = self
-            .den
-            .checked_mul(rhs.den)
-            .ok_or_else(|| err("Overflow computing denominator"))?;
-        Rational::new(num, den)
+        let num = self.num as i128 * rhs.den as i128 - rhs.num as i128 * self.den as i128;
+        let den = self.den as i128 * rhs.den as i128;
+        from_i128(num, den)
+    }
+
+    /// Checked addition, widening to `i128` so the intermediate cross products
+    /// can't overflow `i64`.
+    pub fn checked_add(&self, rhs: &Rational) -> Result<Rational, ErrorString> {
+        let num = self.num as i128 * rhs.den as i128 + rhs.num as i128 * self.den as i128;
+        let den = self.den as i128 * rhs.den as i128;
+        from_i128(num, den)
+    }
+
+    /// Checked multiplication, widening to `i128` before narrowing the
+    /// reduced result back to `i64`.
+    pub fn checked_mul(&self, rhs: &Rational) -> Result<Rational, ErrorString> {
+        let num = self.num as i128 * rhs.num as i128;
+        let den = self.den as i128 * rhs.den as i128;
+        from_i128(num, den)
+    }
+
+    /// Checked division; errors (rather than panics) on division by zero.
+    pub fn checked_div(&self, rhs: &Rational) -> Result<Rational, ErrorString> {
+        if rhs.num == 0 {
+            return Err(err("Division by zero"));
+        }
+        let num = self.num as i128 * rhs.den as i128;
+        let den = self.den as i128 * rhs.num as i128;
+        from_i128(num, den)
     }
 }
 
@@ -109,14 +169,16 @@ impl FromStr for Rational {
 
 impl PartialOrd for Rational {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        (self.num.checked_mul(other.den)?).partial_cmp(&(other.num.checked_mul(self.den)?))
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Rational {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other)
-            .expect("overflow in Rational comparison")
+        // i64::MAX * i64::MAX can't overflow i128, so this is total and panic‑free.
+        let lhs = self.num as i128 * other.den as i128;
+        let rhs = other.num as i128 * self.den as i128;
+        lhs.cmp(&rhs)
     }
 }
 
@@ -128,3 +190,99 @@ impl Sub for Rational {
             .expect("Rational subtraction failed: overflow or zero denominator")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_add_widens_through_i128_before_reducing_back_into_range() {
+        // cross-multiplying i64::MAX/2's numerator against 1/2's denominator
+        // (i64::MAX * 2) overflows i64, but the sum reduces to an integer
+        // that fits right back into i64.
+        let a = Rational::new(i64::MAX, 2).unwrap();
+        let b = Rational::new(1, 2).unwrap();
+        let sum = a.checked_add(&b).unwrap();
+        let expected = Rational::new(((i64::MAX as i128 + 1) / 2) as i64, 1).unwrap();
+        assert_eq!(sum, expected);
+    }
+
+    #[test]
+    fn checked_sub_near_i64_max_stays_exact() {
+        let a = Rational::new(i64::MAX, 1).unwrap();
+        let b = Rational::new(1, 1).unwrap();
+        assert_eq!(
+            a.checked_sub(&b).unwrap(),
+            Rational::new(i64::MAX - 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn checked_sub_of_equal_large_fractions_is_exactly_zero() {
+        // cross-multiplying denominators (i64::MAX/2 - i64::MAX/2) produces
+        // an (i64::MAX * 2) intermediate that overflows i64 on its own, but
+        // must still reduce to exactly zero.
+        let a = Rational::new(i64::MAX, 2).unwrap();
+        assert_eq!(a.checked_sub(&a).unwrap(), Rational::zero());
+    }
+
+    #[test]
+    fn checked_mul_errors_instead_of_panicking_on_overflow() {
+        // i64::MAX * i64::MAX fits in i128 but not back into i64 once reduced,
+        // so this must return an overflow Err rather than panic.
+        let a = Rational::new(i64::MAX, 1).unwrap();
+        assert!(a.checked_mul(&a).is_err());
+    }
+
+    #[test]
+    fn checked_mul_reduces_before_narrowing_so_it_can_still_succeed() {
+        // i64::MAX * 2 overflows i64, but the common factor of 2 cancels with
+        // the denominator before narrowing back to i64, so this must succeed.
+        let a = Rational::new(i64::MAX, 2).unwrap();
+        let b = Rational::new(2, 1).unwrap();
+        assert_eq!(
+            a.checked_mul(&b).unwrap(),
+            Rational::new(i64::MAX, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn checked_div_by_zero_errors() {
+        let a = Rational::new(1, 1).unwrap();
+        assert!(a.checked_div(&Rational::zero()).is_err());
+    }
+
+    #[test]
+    fn checked_div_widens_through_i128_without_panicking() {
+        let a = Rational::new(i64::MAX, 3).unwrap();
+        let b = Rational::new(2, 1).unwrap();
+        let quotient = a.checked_div(&b).unwrap();
+        assert_eq!(quotient, Rational::new(i64::MAX, 6).unwrap());
+    }
+
+    #[test]
+    fn ord_compares_cross_denominators_near_i64_max_without_panicking() {
+        // same cross-multiplication path `Ord::cmp` widens to i128 for: b reduces to
+        // ((i64::MAX - 1) / 2) / 1, so this is i64::MAX vs ((i64::MAX - 1) / 2) * 3.
+        let a = Rational::new(i64::MAX, 3).unwrap();
+        let b = Rational::new(i64::MAX - 1, 2).unwrap();
+        assert!(a < b);
+        assert!(b > a);
+        assert_eq!(a, Rational::new(i64::MAX, 3).unwrap());
+    }
+
+    #[test]
+    fn zero_and_one_are_identities() {
+        let a = Rational::new(5, 7).unwrap();
+        assert_eq!(a.checked_add(&Rational::zero()).unwrap(), a);
+        assert_eq!(a.checked_mul(&Rational::one()).unwrap(), a);
+    }
+
+    #[test]
+    fn sign_helpers_match_the_numerator() {
+        assert!(Rational::new(-3, 4).unwrap().is_negative());
+        assert!(!Rational::new(-3, 4).unwrap().is_sign_positive());
+        assert!(Rational::new(3, 4).unwrap().is_sign_positive());
+        assert!(Rational::zero().is_sign_positive());
+    }
+}